@@ -0,0 +1,59 @@
+use std::{borrow::Cow, str};
+
+/// Decodes raw object bytes into text according to a declared (or guessed) charset.
+///
+/// Tag/commit messages produced by non-UTF-8 tooling aren't always valid UTF-8, so
+/// even without a declared encoding to consult, callers need somewhere to turn
+/// bytes into text without risking a panic. The dependency-light default below
+/// only understands UTF-8 and a lossy Latin-1 fallback; link an encoding crate and
+/// implement this trait to support the full set of labels git accepts.
+///
+/// `encoding` exists for a commit's `encoding` header (e.g. `ISO-8859-1`), but
+/// there is no `Commit` type in this crate yet to supply it - today every caller
+/// here passes `None` and `encoding` is unused. Wire it up once commit parsing and
+/// its `encoding` header are added.
+pub trait Decoder {
+    /// Decode `input`, which was declared to be in `encoding` (e.g. `b"ISO-8859-1"`
+    /// taken from a commit's `encoding` header), or `None` if no encoding was
+    /// declared and `input` should be guessed instead.
+    fn decode<'a>(&self, input: &'a [u8], encoding: Option<&[u8]>) -> Cow<'a, str>;
+}
+
+/// The dependency-light default decoder: valid UTF-8 is borrowed as-is. Anything
+/// else - regardless of the declared encoding, which this decoder doesn't
+/// understand - is transcoded from Latin-1, a mapping that assigns every byte a
+/// codepoint and therefore never fails.
+#[derive(Default, Clone, Copy)]
+pub struct LossyUtf8Decoder;
+
+impl Decoder for LossyUtf8Decoder {
+    fn decode<'a>(&self, input: &'a [u8], _encoding: Option<&[u8]>) -> Cow<'a, str> {
+        match str::from_utf8(input) {
+            Ok(s) => Cow::Borrowed(s),
+            Err(_) => Cow::Owned(input.iter().map(|&b| b as char).collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_utf8_is_borrowed_unchanged() {
+        assert_eq!(
+            LossyUtf8Decoder.decode(b"Hello world", None),
+            Cow::Borrowed("Hello world")
+        );
+    }
+
+    #[test]
+    fn invalid_utf8_falls_back_to_latin1_instead_of_panicking() {
+        // 0xff/0xfe are never valid UTF-8 on their own; the old
+        // `str::from_utf8(...).expect(...)` style would panic here.
+        assert_eq!(
+            LossyUtf8Decoder.decode(&[0xff, 0xfe], None),
+            Cow::<str>::Owned("\u{ff}\u{fe}".to_string())
+        );
+    }
+}
@@ -0,0 +1,8 @@
+#[macro_use]
+extern crate quick_error;
+
+pub mod decode;
+pub mod object;
+
+mod time;
+pub use time::{Sign, Time};
@@ -0,0 +1,32 @@
+pub mod parsed;
+
+pub type Id = [u8; 20];
+
+quick_error! {
+    #[derive(Debug)]
+    pub enum Error {
+        InvalidKind(kind: Vec<u8>) {
+            display("Unknown object kind: {:?}", std::str::from_utf8(kind))
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+pub enum Kind {
+    Tree,
+    Blob,
+    Commit,
+    Tag,
+}
+
+impl Kind {
+    pub fn from_bytes(s: &[u8]) -> Result<Kind, Error> {
+        Ok(match s {
+            b"tree" => Kind::Tree,
+            b"blob" => Kind::Blob,
+            b"commit" => Kind::Commit,
+            b"tag" => Kind::Tag,
+            _ => return Err(Error::InvalidKind(s.to_owned())),
+        })
+    }
+}
@@ -1,15 +1,32 @@
-use crate::{object, Sign, Time};
+use crate::{
+    decode::{Decoder, LossyUtf8Decoder},
+    object, Sign, Time,
+};
 use hex::FromHex;
-use std::str;
+use nom::{
+    bytes::streaming::{tag, take_until, take_while_m_n},
+    character::streaming::{line_ending, not_line_ending},
+    combinator::complete,
+    error::{context, ErrorKind, ParseError},
+    sequence::{preceded, terminated},
+    Err as NomErr, IResult,
+};
+use std::{borrow::Cow, str};
 
 quick_error! {
     #[derive(Debug)]
     pub enum Error {
-        InvalidObjectKind(kind: Vec<u8>) {
-            display("Unknown object kind: {:?}", std::str::from_utf8(&kind))
-        }
-        ParseError(msg: &'static str, kind: Vec<u8>) {
-            display("{}: {:?}", msg, std::str::from_utf8(&kind))
+        /// `context` names the field being parsed (e.g. "tagger time"), `offset`
+        /// is the byte position where parsing gave up - within the whole tag for
+        /// header line errors, within the tagger line for signature errors and
+        /// within the offset text for timezone errors - and `found` is
+        /// the offending byte, if any - none of which require allocating a copy
+        /// of the input to report.
+        ParseError(context: &'static str, offset: usize, expected: &'static str, found: Option<u8>) {
+            display("parse error at byte {} in '{}': expected {}, found {}", offset, context, expected, match found {
+                Some(b) => format!("{:?}", *b as char),
+                None => "end of input".to_string(),
+            })
         }
         ObjectKind(err: object::Error) {
             from()
@@ -18,8 +35,8 @@ quick_error! {
     }
 }
 
-const PGP_SIGNATURE_BEGIN: &'static [u8] = b"-----BEGIN PGP SIGNATURE-----";
-const PGP_SIGNATURE_END: &'static [u8] = b"-----END PGP SIGNATURE-----";
+const PGP_SIGNATURE_BEGIN: &[u8] = b"-----BEGIN PGP SIGNATURE-----";
+const PGP_SIGNATURE_END: &[u8] = b"-----END PGP SIGNATURE-----";
 
 #[derive(PartialEq, Eq, Debug, Hash)]
 pub enum Object<'data> {
@@ -51,70 +68,48 @@ pub struct Tag<'data> {
     pub signature: Signature<'data>,
 }
 
-fn split2_at_space(
-    d: &[u8],
-    is_valid: impl FnOnce(&[u8], &[u8]) -> bool,
-) -> Result<(&[u8], &[u8]), Error> {
-    let mut t = d.splitn(2, |&b| b == b' ');
-    Ok(match (t.next(), t.next()) {
-        (Some(t1), Some(t2)) => {
-            if !is_valid(t1, t2) {
-                return Err(Error::ParseError(
-                    "Invalid space separated tokens - validation failed",
-                    d.to_owned(),
-                ));
-            }
-            (t1, t2)
-        }
-        _ => {
-            return Err(Error::ParseError(
-                "Invalid tokens - expected 2 when split at space",
-                d.to_owned(),
-            ))
-        }
-    })
-}
-
+/// Parses a `+HHMM`/`-HHMM` timezone offset into signed seconds and the sign it
+/// was written with. Tolerates the malformed-but-real forms git itself accepts:
+/// an offset longer than four digits (only the leading `HHMM` count), fewer than
+/// four digits (missing digits are treated as `0`), and non-digit trailing noise
+/// - rather than rejecting the whole object over one odd offset.
 fn parse_timezone_offset(d: &str) -> Result<(i32, Sign), Error> {
+    const CONTEXT: &str = "timezone offset";
     let db = d.as_bytes();
-    if d.len() < 5 || !(db[0] == b'+' || db[0] == b'-') {
-        return Err(Error::ParseError(
-            "invalid timezone offset",
-            d.as_bytes().to_owned(),
-        ));
-    }
-    let sign = if db[0] == b'-' {
-        Sign::Minus
-    } else {
-        Sign::Plus
+    let (sign, rest) = match db.split_first() {
+        Some((b'+', rest)) => (Sign::Plus, rest),
+        Some((b'-', rest)) => (Sign::Minus, rest),
+        Some((&other, _)) => {
+            return Err(Error::ParseError(CONTEXT, 0, "a '+' or '-' sign", Some(other)))
+        }
+        None => return Err(Error::ParseError(CONTEXT, 0, "a '+' or '-' sign", None)),
+    };
+    let digit_count = rest.iter().take_while(|b| b.is_ascii_digit()).count().min(4);
+    let digit_at = |pos: usize| -> i32 {
+        rest.get(pos)
+            .map(|b| (b - b'0') as i32)
+            .filter(|_| pos < digit_count)
+            .unwrap_or(0)
     };
-    let hours = str::from_utf8(&db[..3])
-        .expect("valid utf8")
-        .parse::<i32>()
-        .map_err(|_| Error::ParseError("invalid 'hours' string", db[..3].to_owned()))?;
-    let minutes = str::from_utf8(&db[3..])
-        .expect("valid utf8")
-        .parse::<i32>()
-        .map_err(|_| Error::ParseError("invalid 'minutes' string", db[3..].to_owned()))?;
-    Ok((hours * 3600 + minutes * 60, sign))
-}
-
-fn parse_signature(d: &[u8]) -> Result<Signature, Error> {
+    let hours = digit_at(0) * 10 + digit_at(1);
+    let minutes = digit_at(2) * 10 + digit_at(3);
+    let magnitude = hours * 3600 + minutes * 60;
+    Ok((if sign == Sign::Minus { -magnitude } else { magnitude }, sign))
+}
+
+fn parse_signature(d: &[u8]) -> Result<Signature<'_>, Error> {
     const ONE_SPACE: usize = 1;
     let email_begin = d
         .iter()
         .position(|&b| b == b'<')
-        .ok_or_else(|| {
-            Error::ParseError(
-                "Could not find beginning of email marked by '<'",
-                d.to_owned(),
-            )
-        })
+        .ok_or(Error::ParseError("tagger email", d.len(), "'<'", None))
         .and_then(|pos| {
             if pos == 0 {
                 Err(Error::ParseError(
-                    "Email found in place of author name",
-                    d.to_owned(),
+                    "tagger name",
+                    0,
+                    "a name before '<'",
+                    Some(b'<'),
                 ))
             } else {
                 Ok(pos)
@@ -124,120 +119,365 @@ fn parse_signature(d: &[u8]) -> Result<Signature, Error> {
         + d.iter()
             .skip(email_begin)
             .position(|&b| b == b'>')
-            .ok_or_else(|| {
-                Error::ParseError("Could not find end of email marked by '>'", d.to_owned())
-            })
-            .and_then(|pos| {
-                if pos >= d.len() - 1 - ONE_SPACE {
-                    Err(Error::ParseError(
-                        "There is no time after email",
-                        d.to_owned(),
-                    ))
-                } else {
-                    Ok(pos)
-                }
-            })?;
-    let (time_in_seconds, tzofz) = split2_at_space(&d[email_end + ONE_SPACE + 1..], |_, _| true)
-        .map(|(t1, t2)| {
-            (
-                str::from_utf8(t1).expect("utf-8 encoded time in seconds"),
-                str::from_utf8(t2).expect("utf=8 encoded timezone offset"),
-            )
-        })?;
-    let (offset, sign) = parse_timezone_offset(tzofz)?;
+            .ok_or(Error::ParseError("tagger email", email_begin, "'>'", None))?;
+    // The signature still needs the separating byte after the email's `>` (the
+    // time itself may be missing); check against the bytes actually left
+    // rather than an expression that can underflow on a short `d`.
+    if email_end + ONE_SPACE + 1 > d.len() {
+        return Err(Error::ParseError(
+            "tagger time",
+            d.len(),
+            "a time after the email",
+            None,
+        ));
+    }
+
+    // Tolerate a missing or unparsable timestamp/offset the way git does: fall
+    // back to the epoch/a zero offset rather than aborting the whole object over
+    // one malformed signature line.
+    let mut fields = d[email_end + ONE_SPACE + 1..].splitn(2, |&b| b == b' ');
+    let time = fields
+        .next()
+        .and_then(|t| str::from_utf8(t).ok())
+        .and_then(|t| t.parse::<i64>().ok())
+        .unwrap_or(0);
+    let (offset, sign) = match fields.next().and_then(|tz| str::from_utf8(tz).ok()) {
+        Some(tzofz) => parse_timezone_offset(tzofz).unwrap_or((0, Sign::Plus)),
+        None => (0, Sign::Plus),
+    };
 
     Ok(Signature {
         name: &d[..email_begin - ONE_SPACE],
         email: &d[email_begin + 1..email_end],
         time: Time {
-            time: time_in_seconds.parse::<u32>().map_err(|_| {
-                Error::ParseError(
-                    "Could parse to seconds",
-                    time_in_seconds.as_bytes().to_owned(),
-                )
-            })?,
+            time,
             offset,
             sign,
         },
     })
 }
 
-fn parse_message<'data>(
-    d: &'data [u8],
-    mut lines: impl Iterator<Item = &'data [u8]>,
-) -> Result<(Option<&'data [u8]>, Option<&'data [u8]>), Error> {
-    Ok(match lines.next() {
-        Some(l) if l.len() == 0 => {
-            let msg_begin = 0; // TODO: use nom to parse this or do it without needing nightly
-            if msg_begin >= d.len() {
-                return Err(Error::ParseError(
-                    "Message separator was not followed by message",
-                    d.to_owned(),
-                ));
-            }
-            let mut msg_end = d.len();
-            let mut pgp_signature = None;
-            if let Some(_pgp_begin_line) = lines.find(|l| l.starts_with(PGP_SIGNATURE_BEGIN)) {
-                match lines.find(|l| l.starts_with(PGP_SIGNATURE_END)) {
-                    None => {
-                        return Err(Error::ParseError(
-                            "Didn't find end of signature marker",
-                            d.to_owned(),
-                        ))
-                    }
-                    Some(_) => {
-                        msg_end = d.len(); // TODO: use nom to parse this or do it without needing nightly
-                        pgp_signature = Some(&d[msg_end..])
-                    }
-                }
-            }
-            (Some(&d[msg_begin..msg_end]), pgp_signature)
-        }
-        Some(l) => {
-            return Err(Error::ParseError(
-                "Expected empty newline to separate message",
-                l.to_owned(),
-            ))
+/// A tag's raw, un-validated fields: the values of its `object`, `type`, `tag`
+/// and `tagger` header lines plus its message and PGP signature, split apart
+/// but not yet interpreted.
+struct RawTag<'data> {
+    target_raw: &'data [u8],
+    kind_raw: &'data [u8],
+    name_raw: &'data [u8],
+    tagger_raw: &'data [u8],
+    message: &'data [u8],
+    pgp_signature: Option<&'data [u8]>,
+}
+
+impl<'data> RawTag<'data> {
+    fn into_tag(self) -> Result<Tag<'data>, Error> {
+        Ok(Tag {
+            target_raw: self.target_raw,
+            name_raw: self.name_raw,
+            target_kind: object::Kind::from_bytes(self.kind_raw)?,
+            message: Some(self.message),
+            pgp_signature: self.pgp_signature,
+            signature: parse_signature(self.tagger_raw)?,
+        })
+    }
+}
+
+/// Parses a single `<name> <value>\n` header line, e.g. `type commit\n`, labeling
+/// any parse error with `label` so [`nom_error_at`] can report exactly which header
+/// line was malformed instead of one blanket label for the whole tag. Running out
+/// of input is not labeled: [`Tag::from_bytes`] reports it as a `"tag"` error at
+/// byte 0.
+fn header_line<'a, E: ParseError<&'a [u8]>>(
+    label: &'static str,
+    name: &'static str,
+) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], &'a [u8], E> {
+    move |i: &'a [u8]| context(label, terminated(preceded(tag(name), not_line_ending), line_ending))(i)
+}
+
+fn object_line<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], &'a [u8], E> {
+    context(
+        "object",
+        terminated(
+            preceded(
+                tag("object "),
+                take_while_m_n(40, 40, |b: u8| b.is_ascii_hexdigit()),
+            ),
+            line_ending,
+        ),
+    )(i)
+}
+
+fn type_line<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], &'a [u8], E> {
+    header_line("type", "type ")(i)
+}
+
+fn tag_name_line<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], &'a [u8], E> {
+    header_line("tag", "tag ")(i)
+}
+
+fn tagger_line<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], &'a [u8], E> {
+    header_line("tagger", "tagger ")(i)
+}
+
+/// The four raw header lines of a tag, in order: `object`, `type`, `tag`, `tagger`.
+type HeaderFields<'a> = (&'a [u8], &'a [u8], &'a [u8], &'a [u8]);
+
+fn headers<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], HeaderFields<'a>, E> {
+    let (i, target_raw) = object_line(i)?;
+    let (i, kind_raw) = type_line(i)?;
+    let (i, name_raw) = tag_name_line(i)?;
+    let (i, tagger_raw) = tagger_line(i)?;
+    let (i, _) = line_ending(i)?; // mandatory blank line separating headers from the body
+    Ok((i, (target_raw, kind_raw, name_raw, tagger_raw)))
+}
+
+/// The tag's free-form message and, if present, its armor-delimited PGP signature.
+type MessageAndSignature<'a> = (&'a [u8], Option<&'a [u8]>);
+
+/// Splits the tag body into the free-form `message` and, if present, the
+/// armor-delimited `pgp_signature`, using the real byte offsets of the
+/// `BEGIN`/`END PGP SIGNATURE` markers rather than assuming the whole
+/// remainder belongs to one or the other.
+///
+/// Whether "no `BEGIN PGP SIGNATURE` marker found yet" means "there is no
+/// signature" or "keep waiting for more bytes" depends on whether the caller
+/// has the complete object: `require_complete` selects between the two - only
+/// [`Tag::from_bytes`], which has the whole buffer, may set it.
+fn message_and_signature<'a, E: ParseError<&'a [u8]>>(
+    i: &'a [u8],
+    require_complete: bool,
+) -> IResult<&'a [u8], MessageAndSignature<'a>, E> {
+    let found = if require_complete {
+        complete(take_until(PGP_SIGNATURE_BEGIN))(i)
+    } else {
+        take_until(PGP_SIGNATURE_BEGIN)(i)
+    };
+    match found {
+        Ok((rest, message)) => {
+            let (rest, _) = tag(PGP_SIGNATURE_BEGIN)(rest)?;
+            let (rest, _) = line_ending(rest)?;
+            let (rest, signature) =
+                terminated(take_until(PGP_SIGNATURE_END), tag(PGP_SIGNATURE_END))(rest)?;
+            Ok((rest, (message, Some(signature))))
         }
-        None => (None, None),
-    })
+        Err(NomErr::Error(_)) if require_complete => Ok((&i[i.len()..], (i, None))),
+        Err(e) => Err(e),
+    }
+}
+
+fn raw_tag<'a, E: ParseError<&'a [u8]>>(
+    i: &'a [u8],
+    require_complete: bool,
+) -> IResult<&'a [u8], RawTag<'a>, E> {
+    let (i, (target_raw, kind_raw, name_raw, tagger_raw)) = headers(i)?;
+    let (i, (message, pgp_signature)) = message_and_signature(i, require_complete)?;
+    Ok((
+        i,
+        RawTag {
+            target_raw,
+            kind_raw,
+            name_raw,
+            tagger_raw,
+            message,
+            pgp_signature,
+        },
+    ))
+}
+
+/// The byte offset of `part` within `whole`, given that `part` is a subslice of it.
+fn byte_offset(whole: &[u8], part: &[u8]) -> usize {
+    part.as_ptr() as usize - whole.as_ptr() as usize
+}
+
+/// The nom error type used when parsing a complete tag (see [`Tag::from_bytes`]):
+/// like nom's own `(I, ErrorKind)` it keeps the input where parsing failed, but it
+/// drops the `ErrorKind` and instead keeps the static label of whichever
+/// header line (`"object"`, `"type"`, `"tag"`, `"tagger"`) was being parsed when
+/// the failure happened, attached via the [`context`] combinator.
+#[derive(Debug, Clone, Copy)]
+struct HeaderContext<'a> {
+    label: &'static str,
+    input: &'a [u8],
+}
+
+impl<'a> ParseError<&'a [u8]> for HeaderContext<'a> {
+    fn from_error_kind(input: &'a [u8], _kind: ErrorKind) -> Self {
+        HeaderContext { label: "tag", input }
+    }
+
+    fn append(_input: &'a [u8], _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn add_context(_input: &'a [u8], label: &'static str, other: Self) -> Self {
+        HeaderContext { label, ..other }
+    }
+}
+
+fn nom_error_at(whole: &[u8], e: NomErr<HeaderContext<'_>>) -> Error {
+    match e {
+        NomErr::Error(ctx) | NomErr::Failure(ctx) => Error::ParseError(
+            ctx.label,
+            byte_offset(whole, ctx.input),
+            "a valid tag header line",
+            ctx.input.first().copied(),
+        ),
+        NomErr::Incomplete(_) => Error::ParseError("tag", whole.len(), "more input", None),
+    }
 }
 
 impl<'data> Tag<'data> {
     pub fn target(&self) -> object::Id {
         <[u8; 20]>::from_hex(self.target_raw).expect("prior validation")
     }
+
     pub fn from_bytes(d: &'data [u8]) -> Result<Tag<'data>, Error> {
-        let mut lines = d.split(|&b| b == b'\n');
-        let (target, target_kind, name, signature) =
-            match (lines.next(), lines.next(), lines.next(), lines.next()) {
-                (Some(target), Some(kind), Some(name), Some(tagger)) => {
-                    let (_, target) = split2_at_space(target, |f, v| {
-                        f == b"object" && v.len() == 40 && <[u8; 20]>::from_hex(v).is_ok()
-                    })?;
-                    let kind = split2_at_space(kind, |f, _v| f == b"type")
-                        .and_then(|(_, kind)| object::Kind::from_bytes(kind).map_err(Into::into))?;
-                    let (_, name) = split2_at_space(name, |f, _v| f == b"tag")?;
-                    let (_, tagger) = split2_at_space(tagger, |f, _v| f == b"tagger")?;
-                    (target, kind, name, parse_signature(tagger)?)
-                }
-                _ => {
-                    return Err(Error::ParseError(
-                        "Expected four lines: target, type, tag and tagger",
-                        d.to_owned(),
-                    ))
-                }
-            };
-
-        let (message, pgp_signature) = parse_message(d, &mut lines)?;
+        let (_, raw) =
+            complete(|i| raw_tag(i, true))(d).map_err(|e| nom_error_at(d, e))?;
+        raw.into_tag()
+    }
 
-        Ok(Tag {
-            target_raw: target,
-            name_raw: name,
-            target_kind,
-            message,
-            signature,
-            pgp_signature,
-        })
+    /// Like [`from_bytes()`][Tag::from_bytes], but parses with nom's streaming
+    /// combinators and surfaces `Err(nom::Err::Incomplete)` when the buffer ends
+    /// mid-field instead of treating a short read as a hard parse error. This lets
+    /// a caller feed chunks off a zlib-decompressing reader and resume parsing as
+    /// more bytes arrive, rather than having to buffer the whole tag object first.
+    pub fn from_bytes_streaming(
+        d: &'data [u8],
+    ) -> IResult<&'data [u8], Result<Tag<'data>, Error>> {
+        let (i, raw) = raw_tag(d, false)?;
+        Ok((i, raw.into_tag()))
+    }
+
+    /// Returns [`name_raw`][Tag::name_raw] decoded to text, using the
+    /// dependency-light default heuristic (see [`LossyUtf8Decoder`]). This only
+    /// allocates if the name isn't valid UTF-8. Tags don't carry an `encoding`
+    /// header (only commits do), so this always guesses the charset; see
+    /// [`Decoder`] for the caveat that commit-side encoding support isn't wired
+    /// up yet.
+    pub fn name_decoded(&self) -> Cow<'_, str> {
+        self.name_decoded_with(&LossyUtf8Decoder)
+    }
+
+    /// Like [`name_decoded()`][Tag::name_decoded], but lets the caller plug in a
+    /// [`Decoder`] with full charset label support.
+    pub fn name_decoded_with(&self, decoder: &impl Decoder) -> Cow<'_, str> {
+        decoder.decode(self.name_raw, None)
     }
-}
\ No newline at end of file
+
+    /// Returns [`message`][Tag::message] decoded to text, using the
+    /// dependency-light default heuristic (see [`LossyUtf8Decoder`]). Tags don't
+    /// carry an `encoding` header (only commits do), so this always guesses the
+    /// charset; see [`Decoder`] for the caveat that commit-side encoding support
+    /// isn't wired up yet.
+    pub fn message_decoded(&self) -> Cow<'_, str> {
+        self.message_decoded_with(&LossyUtf8Decoder)
+    }
+
+    /// Like [`message_decoded()`][Tag::message_decoded], but lets the caller plug
+    /// in a [`Decoder`] with full charset label support.
+    pub fn message_decoded_with(&self, decoder: &impl Decoder) -> Cow<'_, str> {
+        decoder.decode(self.message.unwrap_or(&[]), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streaming_waits_for_more_input_instead_of_truncating_the_message() {
+        // Truncated just before the marker arrives - `from_bytes_streaming` must
+        // not guess that there is no signature and hand back a half-finished
+        // message; it should ask the caller for more bytes instead.
+        let d = b"object 0000000000000000000000000000000000000000\n\
+type commit\n\
+tag v1.0\n\
+tagger A U Thor <author@example.com> 1128991234 +0230\n\
+\n\
+Hello world\n--";
+        match Tag::from_bytes_streaming(d) {
+            Err(NomErr::Incomplete(_)) => {}
+            other => panic!("expected Incomplete, got {:?}", other.map(|(_, r)| r)),
+        }
+    }
+
+    #[test]
+    fn timezone_offset_applies_sign_to_both_hours_and_minutes() {
+        // Used to compute hours = -5 (sign embedded via str::parse) and then add
+        // an unsigned +30*60 minutes, yielding -4:30 instead of the correct -5:30.
+        assert_eq!(
+            parse_timezone_offset("-0530").unwrap(),
+            (-19800, Sign::Minus)
+        );
+        assert_eq!(parse_timezone_offset("+0230").unwrap(), (9000, Sign::Plus));
+    }
+
+    #[test]
+    fn timezone_offset_tolerates_the_forms_git_itself_accepts() {
+        // No digits at all after the sign.
+        assert_eq!(parse_timezone_offset("+").unwrap(), (0, Sign::Plus));
+        // More than the usual 4 digits - only the leading HHMM count.
+        assert_eq!(
+            parse_timezone_offset("+023000").unwrap(),
+            (9000, Sign::Plus)
+        );
+    }
+
+    fn sample_tag(tagger: &str, body: &str) -> Vec<u8> {
+        format!(
+            "object {}\ntype commit\ntag v1.0\ntagger {}\n\n{}",
+            "0".repeat(40),
+            tagger,
+            body
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn splits_message_from_pgp_signature_by_real_offset() {
+        let d = sample_tag(
+            "A U Thor <author@example.com> 1128991234 +0230",
+            "a message\n-----BEGIN PGP SIGNATURE-----\ndeadbeef\n-----END PGP SIGNATURE-----\n",
+        );
+        let tag = Tag::from_bytes(&d).unwrap();
+        assert_eq!(tag.message, Some(&b"a message\n"[..]));
+        assert_eq!(tag.pgp_signature, Some(&b"deadbeef\n"[..]));
+    }
+
+    #[test]
+    fn malformed_tagger_line_is_an_error_not_a_panic() {
+        let d = sample_tag("A <>", "message\n");
+        match Tag::from_bytes(&d) {
+            Err(Error::ParseError("tagger time", offset, "a time after the email", None)) => {
+                // The tagger line is an empty email followed by nothing, so parsing gives up right
+                // at the end of the (4-byte) tagger line, not some other position.
+                assert_eq!(offset, "A <>".len());
+            }
+            other => panic!("expected a specific ParseError, got {:?}", other),
+        }
+        assert_eq!(
+            Tag::from_bytes(&d).unwrap_err().to_string(),
+            "parse error at byte 4 in 'tagger time': expected a time after the email, found end of input"
+        );
+    }
+
+    #[test]
+    fn header_line_errors_are_labeled_with_the_specific_field() {
+        // A malformed `type` line must be reported as "type", not the single
+        // blanket "tag" label every header failure used to share.
+        let d = b"object 0000000000000000000000000000000000000000\n\
+not a type line\n\
+tag v1.0\n\
+tagger A U Thor <author@example.com> 1 +0000\n\
+\n\
+msg\n";
+        match Tag::from_bytes(d) {
+            Err(Error::ParseError("type", offset, _, Some(b'n'))) => {
+                // Right after the 40-hex `object` line and its newline.
+                assert_eq!(offset, "object 0000000000000000000000000000000000000000\n".len());
+            }
+            other => panic!("expected a ParseError labeled 'type', got {:?}", other),
+        }
+    }
+}
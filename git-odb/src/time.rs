@@ -0,0 +1,17 @@
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+pub enum Sign {
+    Plus,
+    Minus,
+}
+
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+pub struct Time {
+    /// signed seconds since epoch, allowing dates before 1970 and beyond 2106
+    pub time: i64,
+    /// time offset in seconds, already carrying the sign of `sign`
+    pub offset: i32,
+    /// the sign written in the original `+HHMM`/`-HHMM` offset, kept separately
+    /// since git uses a signed zero (`-0000`) to mean "unknown local offset"
+    /// even though `offset` itself is `0` either way
+    pub sign: Sign,
+}